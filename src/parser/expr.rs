@@ -0,0 +1,214 @@
+use super::tokenizer::{Operator, Span, Token};
+use failure::Fail;
+
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Number(String),
+    Ident(String),
+    /// An identifier followed by `:`, e.g. `start:`. Whitespace and comments
+    /// between the two are skipped, so `start :` is a label as well.
+    Label(String),
+    Binary {
+        op: Operator,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Fail)]
+pub enum ParseError {
+    #[fail(display = "unexpected token: {:?}", found)]
+    UnexpectedToken { found: String, span: Span },
+    #[fail(display = "unexpected end of input")]
+    UnexpectedEnd,
+}
+
+/// True for tokens that carry no meaning to the parser and should be skipped
+/// wherever the grammar expects a significant token.
+fn is_trivia(token: &Token) -> bool {
+    matches!(token, Token::Whitespaces(_) | Token::Comment(_))
+}
+
+/// A cursor over a line's tokens that transparently skips whitespace and comments.
+struct Cursor<'t> {
+    tokens: &'t [(Token, Span)],
+    pos: usize,
+}
+
+impl<'t> Cursor<'t> {
+    fn new(tokens: &'t [(Token, Span)]) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&'t (Token, Span)> {
+        self.tokens[self.pos..]
+            .iter()
+            .find(|(token, _)| !is_trivia(token))
+    }
+
+    fn bump(&mut self) -> Option<&'t (Token, Span)> {
+        while let Some(entry) = self.tokens.get(self.pos) {
+            self.pos += 1;
+            if !is_trivia(&entry.0) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+/// The (left, right) binding power of an infix operator, or `None` if it isn't
+/// one (e.g. `:`, which `parse_primary` handles as a label suffix instead).
+fn infix_binding_power(op: Operator) -> Option<(u8, u8)> {
+    match op {
+        Operator::Plus => Some((1, 2)),
+        Operator::Colon => None,
+    }
+}
+
+fn parse_primary(cursor: &mut Cursor) -> Result<Expr, ParseError> {
+    let (token, span) = cursor.bump().ok_or(ParseError::UnexpectedEnd)?;
+    match token {
+        Token::BinNumber(s)
+        | Token::OctNumber(s)
+        | Token::DecNumber(s)
+        | Token::HexNumber(s)
+        | Token::FloatNumber(s) => Ok(Expr::Number(s.clone())),
+        Token::Identifier(name) => {
+            if let Some((Token::Operator(Operator::Colon), _)) = cursor.peek() {
+                cursor.bump();
+                Ok(Expr::Label(name.clone()))
+            } else {
+                Ok(Expr::Ident(name.clone()))
+            }
+        }
+        other => Err(ParseError::UnexpectedToken {
+            found: format!("{:?}", other),
+            span: *span,
+        }),
+    }
+}
+
+fn parse_expr_bp(cursor: &mut Cursor, min_bp: u8) -> Result<Expr, ParseError> {
+    let mut lhs = parse_primary(cursor)?;
+
+    while let Some((Token::Operator(op), _)) = cursor.peek() {
+        let op = *op;
+        let (left_bp, right_bp) = match infix_binding_power(op) {
+            Some(bp) => bp,
+            None => break,
+        };
+        if left_bp < min_bp {
+            break;
+        }
+
+        cursor.bump();
+        let rhs = parse_expr_bp(cursor, right_bp)?;
+        lhs = Expr::Binary {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        };
+    }
+
+    Ok(lhs)
+}
+
+/// Parse a single expression from a line's tokens using precedence climbing.
+pub fn parse_expr(tokens: &[(Token, Span)]) -> Result<Expr, ParseError> {
+    let mut cursor = Cursor::new(tokens);
+    let expr = parse_expr_bp(&mut cursor, 0)?;
+
+    if let Some((token, span)) = cursor.peek() {
+        return Err(ParseError::UnexpectedToken {
+            found: format!("{:?}", token),
+            span: *span,
+        });
+    }
+
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tokenizer::LineTokenizer;
+
+    #[test]
+    fn test_parse_number() {
+        let lt = LineTokenizer::from_str(0, "100").unwrap();
+        assert_eq!(parse_expr(lt.as_slice()).unwrap(), Expr::Number("100".into()));
+    }
+
+    #[test]
+    fn test_parse_float_number() {
+        let lt = LineTokenizer::from_str(0, "3.14").unwrap();
+        assert_eq!(parse_expr(lt.as_slice()).unwrap(), Expr::Number("3.14".into()));
+
+        let lt = LineTokenizer::from_str(0, "0x1.8p3").unwrap();
+        assert_eq!(
+            parse_expr(lt.as_slice()).unwrap(),
+            Expr::Number("0x1.8p3".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_ident() {
+        let lt = LineTokenizer::from_str(0, "foo").unwrap();
+        assert_eq!(parse_expr(lt.as_slice()).unwrap(), Expr::Ident("foo".into()));
+    }
+
+    #[test]
+    fn test_parse_label() {
+        let lt = LineTokenizer::from_str(0, "start:").unwrap();
+        assert_eq!(
+            parse_expr(lt.as_slice()).unwrap(),
+            Expr::Label("start".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_left_associative_binary() {
+        let lt = LineTokenizer::from_str(0, "1 + 2 + 3").unwrap();
+        assert_eq!(
+            parse_expr(lt.as_slice()).unwrap(),
+            Expr::Binary {
+                op: Operator::Plus,
+                lhs: Box::new(Expr::Binary {
+                    op: Operator::Plus,
+                    lhs: Box::new(Expr::Number("1".into())),
+                    rhs: Box::new(Expr::Number("2".into())),
+                }),
+                rhs: Box::new(Expr::Number("3".into())),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_operand_errors() {
+        let lt = LineTokenizer::from_str(0, "1 + ").unwrap();
+        match parse_expr(lt.as_slice()) {
+            Err(ParseError::UnexpectedEnd) => {}
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_parse_trailing_tokens_error() {
+        let lt = LineTokenizer::from_str(0, "1 2").unwrap();
+        match parse_expr(lt.as_slice()) {
+            Err(ParseError::UnexpectedToken { found, .. }) => {
+                assert_eq!(found, format!("{:?}", Token::DecNumber("2".to_string())))
+            }
+            etc => panic!("{:?}", etc),
+        }
+
+        let lt = LineTokenizer::from_str(0, "1 + 2 3").unwrap();
+        match parse_expr(lt.as_slice()) {
+            Err(ParseError::UnexpectedToken { found, .. }) => {
+                assert_eq!(found, format!("{:?}", Token::DecNumber("3".to_string())))
+            }
+            etc => panic!("{:?}", etc),
+        }
+    }
+}
@@ -1,5 +1,13 @@
 use std::io::BufRead;
 
+mod expr;
+mod tokenizer;
+
+pub use expr::{parse_expr, Expr, ParseError};
+pub use tokenizer::{
+    Error, Operator, Span, StringPrefix, StringQuote, Token, TokenKind, Tokenizer,
+};
+
 #[derive(Debug)]
 pub struct Parser {
     lines: Vec<(usize, String)>,
@@ -1,20 +1,61 @@
-use super::{Operator, Token};
+use super::{Operator, Span, StringPrefix, StringQuote, Token, TokenKind};
 use failure::Fail;
+use std::collections::VecDeque;
 
 #[derive(Debug, Fail)]
 pub enum Error {
     #[fail(display = "invalid character: {:?}, {}", state, c)]
-    InvalidCharacter { state: String, c: char },
+    InvalidCharacter {
+        state: String,
+        c: char,
+        span: Span,
+    },
     #[fail(display = "invalid terminal state: {:?}", state)]
-    InvalidTerminalState { state: String },
+    InvalidTerminalState { state: String, span: Span },
 }
 
-#[derive(Debug)]
-enum StringQuote {
-    Single,
-    Double,
-    Single3,
-    Double3,
+impl Error {
+    pub fn span(&self) -> Span {
+        match self {
+            Error::InvalidCharacter { span, .. } => *span,
+            Error::InvalidTerminalState { span, .. } => *span,
+        }
+    }
+
+    /// Render a three-line caret diagnostic: the offending source line, a `^`
+    /// underline under the span, and the error's own description.
+    pub fn render(&self, source_line: &str) -> String {
+        let span = self.span();
+        let width = (span.end - span.start).max(1);
+        let underline = format!("{}{}", " ".repeat(span.start), "^".repeat(width));
+        format!("{}\n{}\n{}", source_line, underline, self)
+    }
+}
+
+impl StringQuote {
+    fn char(self) -> char {
+        match self {
+            StringQuote::Single | StringQuote::Single3 => '\'',
+            StringQuote::Double | StringQuote::Double3 => '"',
+        }
+    }
+
+    fn len(self) -> usize {
+        match self {
+            StringQuote::Single | StringQuote::Double => 1,
+            StringQuote::Single3 | StringQuote::Double3 => 3,
+        }
+    }
+
+    fn of(c: char, triple: bool) -> Self {
+        match (c, triple) {
+            ('\'', false) => StringQuote::Single,
+            ('\'', true) => StringQuote::Single3,
+            ('"', false) => StringQuote::Double,
+            ('"', true) => StringQuote::Double3,
+            _ => unreachable!("quote char is always ' or \""),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -35,18 +76,21 @@ impl NumberType {
         }
     }
 
-    fn token_builder(self) -> fn(String) -> Token {
+    fn kind(self) -> TokenKind {
         match self {
-            NumberType::Hex => Token::HexNumber,
-            NumberType::Oct => Token::OctNumber,
-            NumberType::Bin => Token::BinNumber,
-            NumberType::Dec => Token::DecNumber,
+            NumberType::Hex => TokenKind::HexNumber,
+            NumberType::Oct => TokenKind::OctNumber,
+            NumberType::Bin => TokenKind::BinNumber,
+            NumberType::Dec => TokenKind::DecNumber,
         }
     }
 }
 
 #[derive(Debug, Copy, Clone)]
 enum NumberState {
+    /// Just consumed the `.` that starts a fraction; no fraction digit yet, so
+    /// an `_` isn't allowed here (it must always sit between two digits).
+    FractionStart,
     Normal,
     Underscore,
 }
@@ -58,11 +102,25 @@ enum State {
     Comment(usize),
     Zero,
     ZeroPadded(usize),
-    StringPrefixSingle,
-    StringPrefixDouble,
-    String(StringQuote, usize),
+    StringPrefixSingle(StringPrefix, usize),
+    StringPrefixDouble(StringPrefix, usize),
+    StringOpen(StringPrefix, StringQuote, u8),
+    String(StringQuote, StringPrefix, usize),
+    StringEscape(StringQuote, StringPrefix, usize),
+    StringEscapeHex(StringQuote, StringPrefix, usize, u8),
+    StringEscapeUnicodeBrace(StringQuote, StringPrefix, usize),
+    StringEscapeUnicodeDigits(StringQuote, StringPrefix, usize),
+    StringClose(u8),
     Whitespaces(usize),
     Number(NumberType, NumberState, usize),
+    /// Scanning digits after the `.` of a decimal or hex float.
+    NumberFraction(NumberType, NumberState, usize),
+    /// Just consumed `e`/`E`/`p`/`P`; an optional sign may follow, then a mandatory digit.
+    NumberExpSign(NumberType, usize),
+    /// Consumed the exponent's sign; a digit is now mandatory.
+    NumberExpDigitRequired(NumberType, usize),
+    /// Scanning the (mandatory, decimal) digits of a float's exponent.
+    NumberExponent(NumberType, NumberState, usize),
     Empty,
 }
 
@@ -72,26 +130,185 @@ impl std::fmt::Display for State {
     }
 }
 
+/// The byte offset a half-finished token (or other open state) started at
+/// within the line, used to build a useful span for `InvalidTerminalState`.
+fn state_starts_at(state: &State) -> Option<usize> {
+    match *state {
+        State::Identifier(starts_at)
+        | State::Comment(starts_at)
+        | State::Whitespaces(starts_at)
+        | State::Number(_, _, starts_at)
+        | State::NumberFraction(_, _, starts_at)
+        | State::NumberExpSign(_, starts_at)
+        | State::NumberExpDigitRequired(_, starts_at)
+        | State::NumberExponent(_, _, starts_at)
+        | State::StringPrefixSingle(_, starts_at)
+        | State::StringPrefixDouble(_, starts_at)
+        | State::String(_, _, starts_at)
+        | State::StringEscape(_, _, starts_at)
+        | State::StringEscapeHex(_, _, starts_at, _)
+        | State::StringEscapeUnicodeBrace(_, _, starts_at)
+        | State::StringEscapeUnicodeDigits(_, _, starts_at) => Some(starts_at),
+        State::Indent | State::Zero | State::ZeroPadded(_) | State::StringOpen(_, _, _)
+        | State::StringClose(_) | State::Empty => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct LineTokenizer {
+    line: usize,
     offset: usize,
-    tokens: Vec<Token>,
+    tokens: Vec<(Token, Span)>,
+}
+
+/// True if `input[index..]` begins with `n` consecutive occurrences of `c`.
+fn quote_run_matches(input: &str, index: usize, c: char, n: usize) -> bool {
+    input[index..].chars().take(n).filter(|&x| x == c).count() == n
+}
+
+/// Decide whether the string opened by the quote char at `index` is a triple-quoted
+/// string, and build the state that scans its body (or, for a triple quote, the
+/// pass-through state that consumes the two remaining opening quote chars first).
+fn open_quote(input: &str, index: usize, c: char, prefix: StringPrefix) -> State {
+    let triple = quote_run_matches(input, index, c, 3);
+    let quote = StringQuote::of(c, triple);
+    if quote.len() > 1 {
+        State::StringOpen(prefix, quote, quote.len() as u8 - 1)
+    } else {
+        State::String(quote, prefix, index + c.len_utf8())
+    }
+}
+
+/// True if the quote char at `index` closes `quote` (for triple quotes, the next
+/// two characters must also be the quote char).
+fn closes_quote(input: &str, index: usize, quote: StringQuote) -> bool {
+    quote_run_matches(input, index, quote.char(), quote.len())
+}
+
+/// Interpret backslash escapes (`\n`, `\t`, `\xNN`, `\u{...}`, ...) in a non-raw
+/// string body. An unrecognized escape is kept verbatim, backslash included,
+/// rather than silently dropping the backslash.
+fn unescape(raw: &str) -> String {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            Some('\\') => result.push('\\'),
+            Some('\'') => result.push('\''),
+            Some('"') => result.push('"'),
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                if let Ok(byte) = u8::from_str_radix(&digits, 16) {
+                    result.push(byte as char);
+                }
+            }
+            Some('u') if chars.peek() == Some(&'{') => {
+                chars.next();
+                let digits: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if let Some(ch) = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+                    result.push(ch);
+                }
+            }
+            Some('u') => {}
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => {}
+        }
+    }
+
+    result
+}
+
+fn build_string(quote: StringQuote, prefix: StringPrefix, raw: &str) -> Token {
+    let value = if prefix.raw {
+        raw.to_string()
+    } else {
+        unescape(raw)
+    };
+    Token::String {
+        quote,
+        prefix,
+        value,
+    }
+}
+
+/// Reconstruct an owned `Token` from the tag `scan` produced and the `&str` it
+/// spans. The thin, allocating half of the adapter that sits on top of the
+/// zero-copy scan shared by `from_str`, `from_str_lossy` and `tokens`.
+fn build_token(kind: TokenKind, text: &str) -> Token {
+    match kind {
+        TokenKind::Comment => Token::Comment(text.to_string()),
+        TokenKind::Identifier => Token::Identifier(text.to_string()),
+        TokenKind::Whitespaces => Token::Whitespaces(text.to_string()),
+        TokenKind::Operator(op) => Token::Operator(op),
+        TokenKind::BinNumber => Token::BinNumber(text.to_string()),
+        TokenKind::OctNumber => Token::OctNumber(text.to_string()),
+        TokenKind::DecNumber => Token::DecNumber(text.to_string()),
+        TokenKind::HexNumber => Token::HexNumber(text.to_string()),
+        TokenKind::FloatNumber => Token::FloatNumber(text.to_string()),
+        TokenKind::String { quote, prefix } => {
+            let body = &text[quote.len()..text.len() - quote.len()];
+            build_string(quote, prefix, body)
+        }
+        TokenKind::Unknown => Token::Unknown(text.to_string()),
+    }
 }
 
 #[inline]
-fn match_first_char(index: usize, c: char) -> (Option<State>, Option<Token>) {
+fn match_first_char(input: &str, index: usize, c: char) -> (Option<State>, Option<TokenKind>) {
     let mut token = None;
     let state = Some(match c.to_ascii_lowercase() {
         '#' => State::Comment(index),
-        'b' | 'f' | 'r' | 'u' => State::StringPrefixSingle,
+        'b' => State::StringPrefixSingle(
+            StringPrefix {
+                bytes: true,
+                ..StringPrefix::default()
+            },
+            index,
+        ),
+        'f' => State::StringPrefixSingle(
+            StringPrefix {
+                format: true,
+                ..StringPrefix::default()
+            },
+            index,
+        ),
+        'r' => State::StringPrefixSingle(
+            StringPrefix {
+                raw: true,
+                ..StringPrefix::default()
+            },
+            index,
+        ),
+        'u' => State::StringPrefixSingle(
+            StringPrefix {
+                unicode: true,
+                ..StringPrefix::default()
+            },
+            index,
+        ),
+        '\'' => open_quote(input, index, '\'', StringPrefix::default()),
+        '"' => open_quote(input, index, '"', StringPrefix::default()),
         '0' => State::Zero,
         ' ' => State::Whitespaces(index),
         ':' => {
-            token = Some(Token::Operator(Operator::Colon));
+            token = Some(TokenKind::Operator(Operator::Colon));
             State::Empty
         }
         '+' => {
-            token = Some(Token::Operator(Operator::Plus));
+            token = Some(TokenKind::Operator(Operator::Plus));
             State::Empty
         }
         c if c.is_numeric() => State::Number(NumberType::Dec, NumberState::Normal, index),
@@ -101,35 +318,45 @@ fn match_first_char(index: usize, c: char) -> (Option<State>, Option<Token>) {
     (state, token)
 }
 
-impl LineTokenizer {
-    pub fn from_str(input: &str) -> Result<Self, Error> {
-        let mut state = State::Indent;
-        let mut offset = 0;
-        let mut tokens = vec![];
+/// Advance the state machine by one character, passing any completed tokens to
+/// `emit`. Shared by the strict (`from_str`), error-recovering (`from_str_lossy`)
+/// and streaming (`tokens`) entry points, which differ in what they do with `Err`.
+#[allow(clippy::too_many_arguments)]
+fn step(
+    state: State,
+    input: &str,
+    index: usize,
+    c: char,
+    line: usize,
+    offset: &mut usize,
+    emit: &mut impl FnMut(TokenKind, Span),
+) -> Result<State, Error> {
+    let span = |start: usize, end: usize| Span { line, start, end };
 
-        for (index, c) in input.char_indices() {
-            state = match (state, c) {
+    Ok(match (state, c) {
                 (State::Indent, ' ') => State::Indent,
                 (s @ State::Indent, c) => {
-                    offset = index;
-                    let (state, token) = match_first_char(index, c);
+                    *offset = index;
+                    let (state, token) = match_first_char(input, index, c);
                     if let Some(token) = token {
-                        tokens.push(token);
+                        emit(token, span(index, index + c.len_utf8()));
                     }
                     state.ok_or(Error::InvalidCharacter {
                         state: s.to_string(),
                         c,
+                        span: span(index, index + c.len_utf8()),
                     })?
                 }
                 (ref s @ State::Whitespaces(starts_at), c) => {
-                    tokens.push(Token::Whitespaces(input[starts_at..index].to_string()));
-                    let (state, token) = match_first_char(index, c);
+                    emit(TokenKind::Whitespaces, span(starts_at, index));
+                    let (state, token) = match_first_char(input, index, c);
                     if let Some(token) = token {
-                        tokens.push(token);
+                        emit(token, span(index, index + c.len_utf8()));
                     }
                     state.ok_or(Error::InvalidCharacter {
                         state: s.to_string(),
                         c,
+                        span: span(index, index + c.len_utf8()),
                     })?
                 }
                 (s @ State::Zero, c) => match c {
@@ -141,40 +368,195 @@ impl LineTokenizer {
                         return Err(Error::InvalidCharacter {
                             state: s.to_string(),
                             c,
+                            span: span(index, index + c.len_utf8()),
                         })
                     }
                 },
-                (State::StringPrefixSingle, c) => {
-                    let p = input[index - 1..].chars().next().unwrap();
-                    match (p.to_ascii_lowercase(), c.to_ascii_lowercase()) {
-                        ('b' | 'f', 'r') | ('r', 'b' | 'f') => State::StringPrefixDouble,
-                        (p, '\'') => State::String(StringQuote::Single, index - 1),
-                        (p, '\"') => State::String(StringQuote::Double, index - 1),
-                        (p, c) => State::Identifier(index - 1),
+                (ref s @ State::StringPrefixSingle(prefix, starts_at), c) => {
+                    match c.to_ascii_lowercase() {
+                        'r' if !prefix.raw && (prefix.bytes || prefix.format) => {
+                            State::StringPrefixDouble(
+                                StringPrefix {
+                                    raw: true,
+                                    ..prefix
+                                },
+                                starts_at,
+                            )
+                        }
+                        'b' if !prefix.bytes && !prefix.format && !prefix.unicode && prefix.raw => {
+                            State::StringPrefixDouble(
+                                StringPrefix {
+                                    bytes: true,
+                                    ..prefix
+                                },
+                                starts_at,
+                            )
+                        }
+                        'f' if !prefix.bytes && !prefix.format && !prefix.unicode && prefix.raw => {
+                            State::StringPrefixDouble(
+                                StringPrefix {
+                                    format: true,
+                                    ..prefix
+                                },
+                                starts_at,
+                            )
+                        }
+                        '\'' => open_quote(input, index, '\'', prefix),
+                        '"' => open_quote(input, index, '"', prefix),
+                        _ if unicode_xid::UnicodeXID::is_xid_continue(c) => {
+                            State::Identifier(starts_at)
+                        }
+                        _ => {
+                            emit(TokenKind::Identifier, span(starts_at, index));
+                            let (state, token) = match_first_char(input, index, c);
+                            if let Some(token) = token {
+                                emit(token, span(index, index + c.len_utf8()));
+                            }
+                            state.ok_or(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })?
+                        }
+                    }
+                }
+                (ref s @ State::StringPrefixDouble(prefix, starts_at), c) => match c {
+                    '\'' => open_quote(input, index, '\'', prefix),
+                    '"' => open_quote(input, index, '"', prefix),
+                    _ if unicode_xid::UnicodeXID::is_xid_continue(c) => State::Identifier(starts_at),
+                    _ => {
+                        emit(TokenKind::Identifier, span(starts_at, index));
+                        let (state, token) = match_first_char(input, index, c);
+                        if let Some(token) = token {
+                            emit(token, span(index, index + c.len_utf8()));
+                        }
+                        state.ok_or(Error::InvalidCharacter {
+                            state: s.to_string(),
+                            c,
+                            span: span(index, index + c.len_utf8()),
+                        })?
+                    }
+                },
+                (State::StringOpen(prefix, quote, remaining), _) => {
+                    if remaining <= 1 {
+                        State::String(quote, prefix, index + quote.char().len_utf8())
+                    } else {
+                        State::StringOpen(prefix, quote, remaining - 1)
+                    }
+                }
+                (State::String(quote, prefix, body_starts_at), c) => match c {
+                    '\\' if !prefix.raw => State::StringEscape(quote, prefix, body_starts_at),
+                    c if c == quote.char() && closes_quote(input, index, quote) => {
+                        let token_starts_at = body_starts_at - quote.len();
+                        let token_ends_at = index + quote.len();
+                        emit(
+                            TokenKind::String { quote, prefix },
+                            span(token_starts_at, token_ends_at),
+                        );
+                        if quote.len() > 1 {
+                            State::StringClose(quote.len() as u8 - 1)
+                        } else {
+                            State::Empty
+                        }
+                    }
+                    _ => State::String(quote, prefix, body_starts_at),
+                },
+                (State::StringClose(remaining), _) => {
+                    if remaining <= 1 {
+                        State::Empty
+                    } else {
+                        State::StringClose(remaining - 1)
+                    }
+                }
+                (State::StringEscape(quote, prefix, body_starts_at), c) => {
+                    match c.to_ascii_lowercase() {
+                        'x' => State::StringEscapeHex(quote, prefix, body_starts_at, 2),
+                        'u' => State::StringEscapeUnicodeBrace(quote, prefix, body_starts_at),
+                        _ => State::String(quote, prefix, body_starts_at),
                     }
                 }
                 (
-                    ref
-                    s
-                    @
-                    State::Number(
-                        t @ (NumberType::Hex | NumberType::Oct | NumberType::Bin),
-                        NumberState::Normal,
-                        starts_at,
-                    ),
+                    ref s @ State::StringEscapeHex(quote, prefix, body_starts_at, remaining),
+                    c,
+                ) => {
+                    if !c.is_ascii_hexdigit() {
+                        return Err(Error::InvalidCharacter {
+                            state: s.to_string(),
+                            c,
+                            span: span(index, index + c.len_utf8()),
+                        });
+                    }
+                    if remaining <= 1 {
+                        State::String(quote, prefix, body_starts_at)
+                    } else {
+                        State::StringEscapeHex(quote, prefix, body_starts_at, remaining - 1)
+                    }
+                }
+                (ref s @ State::StringEscapeUnicodeBrace(quote, prefix, body_starts_at), c) => {
+                    match c {
+                        '{' => State::StringEscapeUnicodeDigits(quote, prefix, body_starts_at),
+                        c => {
+                            return Err(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })
+                        }
+                    }
+                }
+                (ref s @ State::StringEscapeUnicodeDigits(quote, prefix, body_starts_at), c) => {
+                    match c {
+                        '}' => State::String(quote, prefix, body_starts_at),
+                        c if c.is_ascii_hexdigit() => {
+                            State::StringEscapeUnicodeDigits(quote, prefix, body_starts_at)
+                        }
+                        c => {
+                            return Err(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })
+                        }
+                    }
+                }
+                (ref s @ State::Number(NumberType::Hex, NumberState::Normal, starts_at), c) => {
+                    match c {
+                        c if c.is_ascii_hexdigit() => State::Number(NumberType::Hex, NumberState::Normal, starts_at),
+                        '.' => {
+                            State::NumberFraction(NumberType::Hex, NumberState::FractionStart, starts_at)
+                        }
+                        'p' | 'P' => State::NumberExpSign(NumberType::Hex, starts_at),
+                        c => {
+                            emit(TokenKind::HexNumber, span(starts_at, index));
+
+                            let (state, token) = match_first_char(input, index, c);
+                            if let Some(token) = token {
+                                emit(token, span(index, index + c.len_utf8()));
+                            }
+                            state.ok_or(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })?
+                        }
+                    }
+                }
+                (
+                    ref s @ State::Number(t @ (NumberType::Oct | NumberType::Bin), NumberState::Normal, starts_at),
                     c,
                 ) => match c {
                     c if c.is_digit(t.radix()) => State::Number(t, NumberState::Normal, starts_at),
                     c => {
-                        tokens.push(t.token_builder()(input[starts_at..index].to_string()));
+                        emit(t.kind(), span(starts_at, index));
 
-                        let (state, token) = match_first_char(index, c);
+                        let (state, token) = match_first_char(input, index, c);
                         if let Some(token) = token {
-                            tokens.push(token);
+                            emit(token, span(index, index + c.len_utf8()));
                         }
                         state.ok_or(Error::InvalidCharacter {
                             state: s.to_string(),
                             c,
+                            span: span(index, index + c.len_utf8()),
                         })?
                     }
                 },
@@ -186,22 +568,128 @@ impl LineTokenizer {
                         ('_', NumberState::Normal) => {
                             State::Number(NumberType::Dec, NumberState::Underscore, starts_at)
                         }
+                        ('.', NumberState::Normal) => {
+                            State::NumberFraction(NumberType::Dec, NumberState::FractionStart, starts_at)
+                        }
+                        ('e', NumberState::Normal) | ('E', NumberState::Normal) => {
+                            State::NumberExpSign(NumberType::Dec, starts_at)
+                        }
                         (c, NumberState::Normal) => {
-                            tokens.push(Token::HexNumber(input[starts_at..index].to_string()));
+                            emit(TokenKind::DecNumber, span(starts_at, index));
 
-                            let (state, token) = match_first_char(index, c);
+                            let (state, token) = match_first_char(input, index, c);
                             if let Some(token) = token {
-                                tokens.push(token);
+                                emit(token, span(index, index + c.len_utf8()));
                             }
                             state.ok_or(Error::InvalidCharacter {
                                 state: s.to_string(),
                                 c,
+                                span: span(index, index + c.len_utf8()),
                             })?
                         }
                         _ => {
                             return Err(Error::InvalidCharacter {
                                 state: s.to_string(),
                                 c,
+                                span: span(index, index + c.len_utf8()),
+                            });
+                        }
+                    }
+                }
+                (ref s @ State::NumberFraction(t, number_state, starts_at), c) => {
+                    let radix = t.radix();
+                    match (c, number_state) {
+                        (c, _) if c.is_digit(radix) => {
+                            State::NumberFraction(t, NumberState::Normal, starts_at)
+                        }
+                        ('_', NumberState::Normal) => {
+                            State::NumberFraction(t, NumberState::Underscore, starts_at)
+                        }
+                        ('e', NumberState::Normal | NumberState::FractionStart)
+                        | ('E', NumberState::Normal | NumberState::FractionStart)
+                            if matches!(t, NumberType::Dec) =>
+                        {
+                            State::NumberExpSign(NumberType::Dec, starts_at)
+                        }
+                        ('p', NumberState::Normal | NumberState::FractionStart)
+                        | ('P', NumberState::Normal | NumberState::FractionStart)
+                            if matches!(t, NumberType::Hex) =>
+                        {
+                            State::NumberExpSign(NumberType::Hex, starts_at)
+                        }
+                        (c, NumberState::Normal | NumberState::FractionStart)
+                            if matches!(t, NumberType::Dec) =>
+                        {
+                            emit(TokenKind::FloatNumber, span(starts_at, index));
+
+                            let (state, token) = match_first_char(input, index, c);
+                            if let Some(token) = token {
+                                emit(token, span(index, index + c.len_utf8()));
+                            }
+                            state.ok_or(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })?
+                        }
+                        _ => {
+                            // A hex float's fraction must be followed by a `p` exponent,
+                            // and an underscore must always be followed by a digit.
+                            return Err(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            });
+                        }
+                    }
+                }
+                (ref s @ State::NumberExpSign(t, starts_at), c) => match c {
+                    '+' | '-' => State::NumberExpDigitRequired(t, starts_at),
+                    c if c.is_ascii_digit() => State::NumberExponent(t, NumberState::Normal, starts_at),
+                    c => {
+                        return Err(Error::InvalidCharacter {
+                            state: s.to_string(),
+                            c,
+                            span: span(index, index + c.len_utf8()),
+                        })
+                    }
+                },
+                (ref s @ State::NumberExpDigitRequired(t, starts_at), c) => match c {
+                    c if c.is_ascii_digit() => State::NumberExponent(t, NumberState::Normal, starts_at),
+                    c => {
+                        return Err(Error::InvalidCharacter {
+                            state: s.to_string(),
+                            c,
+                            span: span(index, index + c.len_utf8()),
+                        })
+                    }
+                },
+                (ref s @ State::NumberExponent(t, number_state, starts_at), c) => {
+                    match (c, number_state) {
+                        (c, _) if c.is_ascii_digit() => {
+                            State::NumberExponent(t, NumberState::Normal, starts_at)
+                        }
+                        ('_', NumberState::Normal) => {
+                            State::NumberExponent(t, NumberState::Underscore, starts_at)
+                        }
+                        (c, NumberState::Normal) => {
+                            emit(TokenKind::FloatNumber, span(starts_at, index));
+
+                            let (state, token) = match_first_char(input, index, c);
+                            if let Some(token) = token {
+                                emit(token, span(index, index + c.len_utf8()));
+                            }
+                            state.ok_or(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
+                            })?
+                        }
+                        _ => {
+                            return Err(Error::InvalidCharacter {
+                                state: s.to_string(),
+                                c,
+                                span: span(index, index + c.len_utf8()),
                             });
                         }
                     }
@@ -211,25 +699,27 @@ impl LineTokenizer {
                         State::Identifier(starts_at)
                     }
                     c => {
-                        tokens.push(Token::Identifier(input[starts_at..index].to_string()));
-                        let (state, token) = match_first_char(index, c);
+                        emit(TokenKind::Identifier, span(starts_at, index));
+                        let (state, token) = match_first_char(input, index, c);
                         if let Some(token) = token {
-                            tokens.push(token);
+                            emit(token, span(index, index + c.len_utf8()));
                         }
                         state.ok_or(Error::InvalidCharacter {
                             state: s.to_string(),
                             c,
+                            span: span(index, index + c.len_utf8()),
                         })?
                     }
                 },
                 (ref s @ State::Empty, c) => {
-                    let (state, token) = match_first_char(index, c);
+                    let (state, token) = match_first_char(input, index, c);
                     if let Some(token) = token {
-                        tokens.push(token);
+                        emit(token, span(index, index + c.len_utf8()));
                     }
                     state.ok_or(Error::InvalidCharacter {
                         state: s.to_string(),
                         c,
+                        span: span(index, index + c.len_utf8()),
                     })?
                 }
                 (State::Comment(starts_at), _) => State::Comment(starts_at),
@@ -237,29 +727,206 @@ impl LineTokenizer {
                     return Err(Error::InvalidCharacter {
                         state: state.to_string(),
                         c,
+                        span: span(index, index + c.len_utf8()),
                     });
                 }
-            }
+    })
+}
+
+/// Flush whatever token the state machine was still mid-way through when a line
+/// ran out of characters (e.g. a trailing identifier or number with no delimiter).
+fn finish(
+    state: State,
+    input: &str,
+    line: usize,
+    emit: &mut impl FnMut(TokenKind, Span),
+) -> Result<(), Error> {
+    let span = |start: usize, end: usize| Span { line, start, end };
+
+    match state {
+        State::Comment(starts_at) => emit(TokenKind::Comment, span(starts_at, input.len())),
+        State::Number(t, NumberState::Normal, starts_at) => {
+            emit(t.kind(), span(starts_at, input.len()))
         }
-        match state {
-            State::Comment(starts_at) => {
-                tokens.push(Token::Comment(input[starts_at..].to_string()))
-            }
-            State::Number(t, NumberState::Normal, starts_at) => {
-                tokens.push(t.token_builder()(input[starts_at..].to_string()))
+        State::NumberFraction(
+            NumberType::Dec,
+            NumberState::Normal | NumberState::FractionStart,
+            starts_at,
+        ) => emit(TokenKind::FloatNumber, span(starts_at, input.len())),
+        State::NumberExponent(_, NumberState::Normal, starts_at) => {
+            emit(TokenKind::FloatNumber, span(starts_at, input.len()))
+        }
+        State::Indent | State::Whitespaces(_) | State::Empty => (),
+        State::Identifier(starts_at) => {
+            emit(TokenKind::Identifier, span(starts_at, input.len()))
+        }
+        State::StringPrefixSingle(_, starts_at) | State::StringPrefixDouble(_, starts_at) => {
+            emit(TokenKind::Identifier, span(starts_at, input.len()))
+        }
+        state => {
+            let starts_at = state_starts_at(&state).unwrap_or(input.len());
+            return Err(Error::InvalidTerminalState {
+                state: state.to_string(),
+                span: span(starts_at, input.len()),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+impl LineTokenizer {
+    pub fn from_str(line: usize, input: &str) -> Result<Self, Error> {
+        let mut state = State::Indent;
+        let mut offset = 0;
+        let mut kinds = vec![];
+
+        for (index, c) in input.char_indices() {
+            state = step(state, input, index, c, line, &mut offset, &mut |kind, span| {
+                kinds.push((kind, span))
+            })?;
+        }
+
+        finish(state, input, line, &mut |kind, span| kinds.push((kind, span)))?;
+
+        let tokens = kinds
+            .into_iter()
+            .map(|(kind, span)| (build_token(kind, &input[span.start..span.end]), span))
+            .collect();
+
+        Ok(Self {
+            line,
+            offset,
+            tokens,
+        })
+    }
+
+    /// Like `from_str`, but never fails: an unrecognized character becomes a
+    /// `Token::Unknown` and scanning resumes right after it. Returns every token
+    /// alongside every error the run encountered, instead of aborting on the first.
+    pub fn from_str_lossy(line: usize, input: &str) -> (Self, Vec<Error>) {
+        let mut state = State::Indent;
+        let mut offset = 0;
+        let mut kinds = vec![];
+        let mut errors = vec![];
+
+        for (index, c) in input.char_indices() {
+            state = match step(state, input, index, c, line, &mut offset, &mut |kind, span| {
+                kinds.push((kind, span))
+            }) {
+                Ok(next) => next,
+                Err(err) => {
+                    kinds.push((TokenKind::Unknown, err.span()));
+                    errors.push(err);
+                    State::Empty
+                }
+            };
+        }
+
+        if let Err(err) = finish(state, input, line, &mut |kind, span| kinds.push((kind, span))) {
+            errors.push(err);
+        }
+
+        let tokens = kinds
+            .into_iter()
+            .map(|(kind, span)| (build_token(kind, &input[span.start..span.end]), span))
+            .collect();
+
+        (
+            Self {
+                line,
+                offset,
+                tokens,
+            },
+            errors,
+        )
+    }
+
+    /// The tokens lexed from this line, each paired with the span it occupies.
+    pub fn as_slice(&self) -> &[(Token, Span)] {
+        &self.tokens
+    }
+
+    /// The zero-based index of the source line this was tokenized from.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The byte offset of this line's first non-indent character.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Lazily tokenize `input` without allocating per-token text: each item
+    /// pairs a `TokenKind` tag with the `&str` slice of `input` it spans,
+    /// computed one character at a time as the iterator is driven. As with
+    /// `from_str_lossy`, an unrecognized character is recovered as
+    /// `TokenKind::Unknown` rather than aborting; only an invalid end-of-line
+    /// state (e.g. an unterminated string) surfaces as `Err`.
+    pub fn tokens(input: &str) -> impl Iterator<Item = Result<(TokenKind, &str), Error>> {
+        LineTokens {
+            input,
+            chars: input.char_indices(),
+            state: Some(State::Indent),
+            offset: 0,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+}
+
+/// The iterator behind `LineTokenizer::tokens`. Drives `step`/`finish` one
+/// character at a time, buffering in `pending` the rare case where a single
+/// character completes one token and immediately starts another (e.g. a digit
+/// run ending in `:`).
+struct LineTokens<'t> {
+    input: &'t str,
+    chars: std::str::CharIndices<'t>,
+    state: Option<State>,
+    offset: usize,
+    pending: VecDeque<Result<(TokenKind, Span), Error>>,
+    finished: bool,
+}
+
+impl<'t> Iterator for LineTokens<'t> {
+    type Item = Result<(TokenKind, &'t str), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item.map(|(kind, span)| (kind, &self.input[span.start..span.end])));
             }
-            State::Indent | State::Whitespaces(_) | State::Empty => (),
-            State::Identifier(starts_at) => {
-                tokens.push(Token::Identifier(input[starts_at..].to_string()))
+            if self.finished {
+                return None;
             }
-            state => {
-                return Err(Error::InvalidTerminalState {
-                    state: state.to_string(),
-                })
+
+            let input = self.input;
+            let pending = &mut self.pending;
+
+            match self.chars.next() {
+                Some((index, c)) => {
+                    let state = self.state.take().unwrap_or(State::Empty);
+                    match step(state, input, index, c, 0, &mut self.offset, &mut |kind, span| {
+                        pending.push_back(Ok((kind, span)))
+                    }) {
+                        Ok(next_state) => self.state = Some(next_state),
+                        Err(err) => {
+                            pending.push_back(Ok((TokenKind::Unknown, err.span())));
+                            self.state = Some(State::Empty);
+                        }
+                    }
+                }
+                None => {
+                    self.finished = true;
+                    let state = self.state.take().unwrap_or(State::Empty);
+                    if let Err(err) =
+                        finish(state, input, 0, &mut |kind, span| pending.push_back(Ok((kind, span))))
+                    {
+                        pending.push_back(Err(err));
+                    }
+                }
             }
         }
-
-        Ok(Self { offset, tokens })
     }
 }
 
@@ -267,9 +934,13 @@ impl LineTokenizer {
 mod tests {
     use super::*;
 
+    fn kinds(lt: &LineTokenizer) -> Vec<&Token> {
+        lt.tokens.iter().map(|(token, _)| token).collect()
+    }
+
     #[test]
     fn test_simple_number() {
-        match LineTokenizer::from_str("100").unwrap().tokens.as_slice() {
+        match kinds(&LineTokenizer::from_str(0, "100").unwrap()).as_slice() {
             [Token::DecNumber(num)] => {
                 assert_eq!(num, "100", "{}", num);
             }
@@ -278,11 +949,7 @@ mod tests {
             }
         }
 
-        match LineTokenizer::from_str("100_000_000")
-            .unwrap()
-            .tokens
-            .as_slice()
-        {
+        match kinds(&LineTokenizer::from_str(0, "100_000_000").unwrap()).as_slice() {
             [Token::DecNumber(num)] => {
                 assert_eq!(num, "100_000_000", "{}", num);
             }
@@ -291,17 +958,13 @@ mod tests {
             }
         }
 
-        assert!(LineTokenizer::from_str("100_000_000_").is_err());
-        assert!(LineTokenizer::from_str("100__000_000").is_err());
+        assert!(LineTokenizer::from_str(0, "100_000_000_").is_err());
+        assert!(LineTokenizer::from_str(0, "100__000_000").is_err());
     }
 
     #[test]
     fn test_numbers() {
-        match LineTokenizer::from_str("0x10fF + 0x1234")
-            .unwrap()
-            .tokens
-            .as_slice()
-        {
+        match kinds(&LineTokenizer::from_str(0, "0x10fF + 0x1234").unwrap()).as_slice() {
             [Token::HexNumber(hex_a), Token::Whitespaces(space_a), Token::Operator(Operator::Plus), Token::Whitespaces(space_b), Token::HexNumber(hex_b)] =>
             {
                 assert_eq!(hex_a, "0x10fF", "{}", hex_a);
@@ -314,11 +977,7 @@ mod tests {
             }
         }
 
-        match LineTokenizer::from_str("0xff+0x01")
-            .unwrap()
-            .tokens
-            .as_slice()
-        {
+        match kinds(&LineTokenizer::from_str(0, "0xff+0x01").unwrap()).as_slice() {
             [Token::HexNumber(hex_a), Token::Operator(Operator::Plus), Token::HexNumber(hex_b)] => {
                 assert_eq!(hex_a, "0xff", "{}", hex_a);
                 assert_eq!(hex_b, "0x01", "{}", hex_b);
@@ -328,11 +987,7 @@ mod tests {
             }
         }
 
-        match LineTokenizer::from_str("0b01001 + 0o1234+0x1010")
-            .unwrap()
-            .tokens
-            .as_slice()
-        {
+        match kinds(&LineTokenizer::from_str(0, "0b01001 + 0o1234+0x1010").unwrap()).as_slice() {
             [Token::BinNumber(bin_a), Token::Whitespaces(space_a), Token::Operator(Operator::Plus), Token::Whitespaces(space_b), Token::OctNumber(oct_a), Token::Operator(Operator::Plus), Token::HexNumber(hex_a)] =>
             {
                 assert_eq!(bin_a, "0b01001", "{}", bin_a);
@@ -346,4 +1001,356 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_simple_strings() {
+        match kinds(&LineTokenizer::from_str(0, "'hello'").unwrap()).as_slice() {
+            [Token::String {
+                quote: StringQuote::Single,
+                prefix,
+                value,
+            }] => {
+                assert_eq!(value, "hello", "{}", value);
+                assert_eq!(*prefix, StringPrefix::default());
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "\"hello\" + \"world\"").unwrap()).as_slice() {
+            [Token::String { value: a, .. }, Token::Whitespaces(_), Token::Operator(Operator::Plus), Token::Whitespaces(_), Token::String { value: b, .. }] =>
+            {
+                assert_eq!(a, "hello", "{}", a);
+                assert_eq!(b, "world", "{}", b);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "''").unwrap()).as_slice() {
+            [Token::String { value, .. }] => {
+                assert_eq!(value, "", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_triple_quoted_strings() {
+        match kinds(&LineTokenizer::from_str(0, "'''hello 'world' today'''").unwrap()).as_slice() {
+            [Token::String {
+                quote: StringQuote::Single3,
+                value,
+                ..
+            }] => {
+                assert_eq!(value, "hello 'world' today", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, r#""""""""#).unwrap()).as_slice() {
+            [Token::String {
+                quote: StringQuote::Double3,
+                value,
+                ..
+            }] => {
+                assert_eq!(value, "", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        match kinds(&LineTokenizer::from_str(0, r#""a\nb\tc\\d\"e""#).unwrap()).as_slice() {
+            [Token::String { value, .. }] => {
+                assert_eq!(value, "a\nb\tc\\d\"e", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, r#""\x41\u{1F600}""#).unwrap()).as_slice() {
+            [Token::String { value, .. }] => {
+                assert_eq!(value.chars().next(), Some('A'));
+                assert_eq!(value.chars().nth(1), Some('\u{1F600}'));
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, r#""\z""#).unwrap()).as_slice() {
+            [Token::String { value, .. }] => {
+                assert_eq!(value, "\\z", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_prefixes() {
+        match kinds(&LineTokenizer::from_str(0, "r'no\\nescape'").unwrap()).as_slice() {
+            [Token::String { prefix, value, .. }] => {
+                assert!(prefix.raw);
+                assert_eq!(value, "no\\nescape", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "rb\"bytes\"").unwrap()).as_slice() {
+            [Token::String { prefix, value, .. }] => {
+                assert!(prefix.raw);
+                assert!(prefix.bytes);
+                assert_eq!(value, "bytes", "{}", value);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "b").unwrap()).as_slice() {
+            [Token::Identifier(ident)] => {
+                assert_eq!(ident, "b", "{}", ident);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_prefix_terminator_is_not_folded_into_identifier() {
+        match kinds(&LineTokenizer::from_str(0, "f+g").unwrap()).as_slice() {
+            [
+                Token::Identifier(a),
+                Token::Operator(Operator::Plus),
+                Token::Identifier(b),
+            ] => {
+                assert_eq!(a, "f");
+                assert_eq!(b, "g");
+            }
+            etc => panic!("{:?}", etc),
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "b:c").unwrap()).as_slice() {
+            [
+                Token::Identifier(a),
+                Token::Operator(Operator::Colon),
+                Token::Identifier(b),
+            ] => {
+                assert_eq!(a, "b");
+                assert_eq!(b, "c");
+            }
+            etc => panic!("{:?}", etc),
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "f 1").unwrap()).as_slice() {
+            [
+                Token::Identifier(ident),
+                Token::Whitespaces(_),
+                Token::DecNumber(n),
+            ] => {
+                assert_eq!(ident, "f");
+                assert_eq!(n, "1");
+            }
+            etc => panic!("{:?}", etc),
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "b#comment").unwrap()).as_slice() {
+            [Token::Identifier(ident), Token::Comment(comment)] => {
+                assert_eq!(ident, "b");
+                assert_eq!(comment, "#comment");
+            }
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_error_span_points_at_offending_char() {
+        let err = LineTokenizer::from_str(0, "100 @ 200").unwrap_err();
+        match err {
+            Error::InvalidCharacter { span, c, .. } => {
+                assert_eq!(c, '@');
+                assert_eq!(span, Span { line: 0, start: 4, end: 5 });
+            }
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_render_diagnostic() {
+        let err = LineTokenizer::from_str(0, "100 @ 200").unwrap_err();
+        let rendered = err.render("100 @ 200");
+        assert_eq!(
+            rendered,
+            "100 @ 200\n    ^\ninvalid character: \"Whitespaces(3)\", @"
+        );
+    }
+
+    #[test]
+    fn test_from_str_lossy_recovers_from_unknown_char() {
+        let (lt, errors) = LineTokenizer::from_str_lossy(0, "0x10fF @ 0b01001");
+        match kinds(&lt).as_slice() {
+            [Token::HexNumber(hex), Token::Whitespaces(_), Token::Unknown(unk), Token::Whitespaces(_), Token::BinNumber(bin)] =>
+            {
+                assert_eq!(hex, "0x10fF", "{}", hex);
+                assert_eq!(unk, "@", "{}", unk);
+                assert_eq!(bin, "0b01001", "{}", bin);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            Error::InvalidCharacter { c, .. } => assert_eq!(*c, '@'),
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_float_numbers() {
+        match kinds(&LineTokenizer::from_str(0, "3.14").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "3.14", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "6.022e23").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "6.022e23", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "1e9").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "1e9", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_float_number() {
+        match kinds(&LineTokenizer::from_str(0, "0x1.8p3").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "0x1.8p3", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+
+        match kinds(&LineTokenizer::from_str(0, "0x1p3").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "0x1p3", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_float_requires_exponent() {
+        assert!(LineTokenizer::from_str(0, "0x1.8").is_err());
+    }
+
+    #[test]
+    fn test_underscore_immediately_after_dot_errors() {
+        assert!(LineTokenizer::from_str(0, "1._5").is_err());
+        assert!(LineTokenizer::from_str(0, "0x1._8p3").is_err());
+    }
+
+    #[test]
+    fn test_trailing_dot_is_deterministic() {
+        match kinds(&LineTokenizer::from_str(0, "3.").unwrap()).as_slice() {
+            [Token::FloatNumber(num)] => {
+                assert_eq!(num, "3.", "{}", num);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_terminator_labels_dec_not_hex() {
+        let (lt, errors) = LineTokenizer::from_str_lossy(0, "100@200");
+        match kinds(&lt).as_slice() {
+            [Token::DecNumber(first), Token::Unknown(_), Token::DecNumber(second)] => {
+                assert_eq!(first, "100", "{}", first);
+                assert_eq!(second, "200", "{}", second);
+            }
+            etc => {
+                panic!("{:?}", etc);
+            }
+        }
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_tokens_zero_copy_matches_slices() {
+        let input = "100 + foo";
+        let tokens = LineTokenizer::tokens(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        match tokens.as_slice() {
+            [
+                (TokenKind::DecNumber, "100"),
+                (TokenKind::Whitespaces, " "),
+                (TokenKind::Operator(Operator::Plus), "+"),
+                (TokenKind::Whitespaces, " "),
+                (TokenKind::Identifier, "foo"),
+            ] => {}
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_tokens_zero_copy_recovers_from_unknown_char() {
+        let input = "0x10fF @ 0b01001";
+        let tokens = LineTokenizer::tokens(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        match tokens.as_slice() {
+            [
+                (TokenKind::HexNumber, "0x10fF"),
+                (TokenKind::Whitespaces, " "),
+                (TokenKind::Unknown, "@"),
+                (TokenKind::Whitespaces, " "),
+                (TokenKind::BinNumber, "0b01001"),
+            ] => {}
+            etc => panic!("{:?}", etc),
+        }
+    }
+
+    #[test]
+    fn test_tokens_zero_copy_reports_unterminated_string_error() {
+        let input = "'unterminated";
+        let result = LineTokenizer::tokens(input).collect::<Result<Vec<_>, _>>();
+        assert!(result.is_err());
+    }
 }
@@ -1,13 +1,33 @@
 mod line;
 
-use line::LineTokenizer;
+pub use line::{Error, LineTokenizer};
 
-#[derive(Debug)]
+use std::io::BufRead;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Operator {
     Colon,
     Plus,
 }
 
+/// The quote style a string literal was opened with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StringQuote {
+    Single,
+    Double,
+    Single3,
+    Double3,
+}
+
+/// The prefix flags a string literal was tagged with, e.g. `r"..."` or `rb"..."`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct StringPrefix {
+    pub raw: bool,
+    pub bytes: bool,
+    pub format: bool,
+    pub unicode: bool,
+}
+
 #[derive(Debug)]
 pub enum Token {
     Comment(String),
@@ -18,6 +38,46 @@ pub enum Token {
     OctNumber(String),
     DecNumber(String),
     HexNumber(String),
+    /// A decimal float (`3.14`, `6.022e23`) or pspp-style hex float (`0x1.8p3`).
+    FloatNumber(String),
+    String {
+        quote: StringQuote,
+        prefix: StringPrefix,
+        value: String,
+    },
+    /// A character (or short run of characters) that didn't match any token rule.
+    /// Only produced by `Tokenizer::tokenize_lossy` / `LineTokenizer::from_str_lossy`.
+    Unknown(String),
+}
+
+/// A byte range within a single line of source, identified by its line index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The same shape as `Token`, minus the owned text: a tag plus (for the variants
+/// that need it) the metadata that isn't recoverable from the source slice alone.
+/// Paired with the `&str` it spans, this is everything `Token` carries, without
+/// allocating. Produced by `LineTokenizer::tokens`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    Comment,
+    Identifier,
+    Whitespaces,
+    Operator(Operator),
+    BinNumber,
+    OctNumber,
+    DecNumber,
+    HexNumber,
+    FloatNumber,
+    String {
+        quote: StringQuote,
+        prefix: StringPrefix,
+    },
+    Unknown,
 }
 
 #[derive(Debug)]
@@ -25,16 +85,68 @@ pub struct Tokenizer {
     lines: Vec<LineTokenizer>,
 }
 
+/// Lazily tokenizes lines pulled one at a time from a `BufRead`, instead of
+/// buffering the whole file into a `Vec` up front. Built by `Tokenizer::iter_reader`.
+pub struct LineIter<R> {
+    lines: std::io::Lines<R>,
+    idx: usize,
+}
+
+impl<R: BufRead> Iterator for LineIter<R> {
+    type Item = Result<LineTokenizer, failure::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.lines.next()?;
+        let idx = self.idx;
+        self.idx += 1;
+        Some(line.map_err(failure::Error::from).and_then(|line| {
+            LineTokenizer::from_str(idx, &line).map_err(|err| failure::err_msg(err.render(&line)))
+        }))
+    }
+}
+
 impl Tokenizer {
     pub fn from_reader<R>(reader: R) -> Result<Self, failure::Error>
+    where
+        R: BufRead,
+    {
+        Ok(Self {
+            lines: Self::iter_reader(reader).collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+
+    /// Stream `LineTokenizer`s one line at a time as `reader` is read, rather
+    /// than collecting the whole file into a `Vec` first.
+    pub fn iter_reader<R>(reader: R) -> LineIter<R>
+    where
+        R: BufRead,
+    {
+        LineIter {
+            lines: reader.lines(),
+            idx: 0,
+        }
+    }
+
+    /// Tokenize every line without bailing on the first lexing error: unrecognized
+    /// characters become `Token::Unknown` tokens and scanning resumes right after
+    /// them, so callers get every token plus every error the run encountered. An
+    /// I/O failure reading `reader` is a different kind of error, not something
+    /// `Vec<Error>` can represent, so it still stops the run and is surfaced as
+    /// `Err` rather than silently truncating the output.
+    pub fn tokenize_lossy<R>(reader: R) -> Result<(Vec<LineTokenizer>, Vec<Error>), failure::Error>
     where
         R: std::io::BufRead,
     {
-        let lines = reader
-            .lines()
-            .map(|line| LineTokenizer::from_str(&line?).map_err(|err| failure::Error::from(err)))
-            .collect::<Result<_, _>>()?;
+        let mut lines = vec![];
+        let mut errors = vec![];
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let (tokenizer, line_errors) = LineTokenizer::from_str_lossy(idx, &line);
+            errors.extend(line_errors);
+            lines.push(tokenizer);
+        }
 
-        Ok(Self { lines })
+        Ok((lines, errors))
     }
 }